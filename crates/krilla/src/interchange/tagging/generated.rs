@@ -0,0 +1,398 @@
+// Generated tag struct definitions.
+//
+// In the real toolchain this file is produced by a Python script from the PDF
+// structure type tables (see this module's top-level doc comment). This sandbox
+// copy is maintained by hand and only covers the structure types exercised
+// elsewhere in this crate; regenerate from the full table before relying on it
+// for anything beyond that.
+
+use crate::color::Color;
+
+macro_rules! common_builders {
+    ($name:ident) => {
+        impl $name {
+            /// Sets the element identifier (`/ID`), used by `/IDTree`, `/Ref`, and
+            /// automatic table `/Headers` resolution.
+            pub fn with_id(mut self, id: TagId) -> Self {
+                self.attrs.push(internal::Attr::Id(id));
+                self
+            }
+
+            /// Sets the element's title (`/T`).
+            pub fn with_title(mut self, title: impl Into<String>) -> Self {
+                self.attrs.push(internal::Attr::Title(title.into()));
+                self
+            }
+
+            /// Sets the element's natural language (`/Lang`).
+            pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+                self.attrs.push(internal::Attr::Lang(lang.into()));
+                self
+            }
+
+            /// Sets alternate text (`/Alt`) describing the element's content.
+            pub fn with_alt_text(mut self, alt_text: impl Into<String>) -> Self {
+                self.attrs.push(internal::Attr::AltText(alt_text.into()));
+                self
+            }
+
+            /// Sets the expanded form (`/E`) of an abbreviation or acronym.
+            pub fn with_expanded(mut self, expanded: impl Into<String>) -> Self {
+                self.attrs.push(internal::Attr::Expanded(expanded.into()));
+                self
+            }
+
+            /// Sets replacement text (`/ActualText`) for the element's content.
+            pub fn with_actual_text(mut self, actual_text: impl Into<String>) -> Self {
+                self.attrs.push(internal::Attr::ActualText(actual_text.into()));
+                self
+            }
+
+            /// Sets the heading level for `Hn` elements.
+            pub fn with_heading_level(mut self, level: NonZeroU32) -> Self {
+                self.attrs.push(internal::Attr::HeadingLevel(level));
+                self
+            }
+
+            /// Sets the other structure elements this element refers to (`/Ref`),
+            /// e.g. linking a footnote reference to the `Note` it points at. Each
+            /// id is resolved to the referenced element's indirect reference at
+            /// serialization time.
+            pub fn with_refs(mut self, refs: SmallVec<[TagId; 1]>) -> Self {
+                self.attrs.push(internal::Attr::Ref(refs));
+                self
+            }
+        }
+    };
+}
+
+macro_rules! layout_builders {
+    ($name:ident) => {
+        impl $name {
+            /// Sets the positioning of the element (`/Placement`).
+            pub fn with_placement(mut self, value: Placement) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::Placement(value));
+                self
+            }
+
+            /// Sets the layout progression directions (`/WritingMode`).
+            pub fn with_writing_mode(mut self, value: WritingMode) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::WritingMode(value));
+                self
+            }
+
+            /// Sets the element's bounding box (`/BBox`).
+            pub fn with_bbox(mut self, value: Rect) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BBox(value));
+                self
+            }
+
+            /// Sets the element's width (`/Width`).
+            pub fn with_width(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::Width(value));
+                self
+            }
+
+            /// Sets the element's height (`/Height`).
+            pub fn with_height(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::Height(value));
+                self
+            }
+
+            /// Sets the background color painted behind the element (`/BackgroundColor`).
+            pub fn with_background_color(mut self, value: Color) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BackgroundColor(value));
+                self
+            }
+
+            /// Sets the border color for each of the element's four sides (`/BorderColor`).
+            pub fn with_border_color(mut self, value: Sides<Color>) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BorderColor(value));
+                self
+            }
+
+            /// Sets the border style for each of the element's four sides (`/BorderStyle`).
+            pub fn with_border_style(mut self, value: Sides<BorderStyle>) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BorderStyle(value));
+                self
+            }
+
+            /// Sets the border thickness for each of the element's four sides (`/BorderThickness`).
+            pub fn with_border_thickness(mut self, value: Sides<f32>) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BorderThickness(value));
+                self
+            }
+
+            /// Sets the padding for each of the element's four sides (`/Padding`).
+            pub fn with_padding(mut self, value: Sides<f32>) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::Padding(value));
+                self
+            }
+
+            /// Sets the color used to paint the element's text and graphics (`/Color`).
+            pub fn with_color(mut self, value: Color) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::Color(value));
+                self
+            }
+
+            /// Sets the space reserved before the element (`/SpaceBefore`).
+            pub fn with_space_before(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::SpaceBefore(value));
+                self
+            }
+
+            /// Sets the space reserved after the element (`/SpaceAfter`).
+            pub fn with_space_after(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::SpaceAfter(value));
+                self
+            }
+
+            /// Sets the indentation of the element's start edge (`/StartIndent`).
+            pub fn with_start_indent(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::StartIndent(value));
+                self
+            }
+
+            /// Sets the indentation of the element's end edge (`/EndIndent`).
+            pub fn with_end_indent(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::EndIndent(value));
+                self
+            }
+
+            /// Sets the additional indentation of the element's first line (`/TextIndent`).
+            pub fn with_text_indent(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::TextIndent(value));
+                self
+            }
+
+            /// Sets the text alignment within the element's lines (`/TextAlign`).
+            pub fn with_text_align(mut self, value: TextAlign) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::TextAlign(value));
+                self
+            }
+
+            /// Sets the block-direction alignment of the element's content (`/BlockAlign`).
+            pub fn with_block_align(mut self, value: BlockAlign) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BlockAlign(value));
+                self
+            }
+
+            /// Sets the inline-direction alignment of the element (`/InlineAlign`).
+            pub fn with_inline_align(mut self, value: InlineAlign) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::InlineAlign(value));
+                self
+            }
+
+            /// Sets the element's baseline shift (`/BaselineShift`).
+            pub fn with_baseline_shift(mut self, value: f32) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::BaselineShift(value));
+                self
+            }
+
+            /// Sets the height of the element's lines (`/LineHeight`).
+            pub fn with_line_height(mut self, value: LineHeight) -> Self {
+                self.layout_attrs.push(internal::LayoutAttr::LineHeight(value));
+                self
+            }
+
+            /// Sets the kind of text decoration line drawn across the element's text
+            /// (`/TextDecorationType`).
+            pub fn with_text_decoration_type(mut self, value: TextDecorationType) -> Self {
+                self.layout_attrs
+                    .push(internal::LayoutAttr::TextDecorationType(value));
+                self
+            }
+
+            /// Sets the color of the element's text decoration line (`/TextDecorationColor`).
+            pub fn with_text_decoration_color(mut self, value: Color) -> Self {
+                self.layout_attrs
+                    .push(internal::LayoutAttr::TextDecorationColor(value));
+                self
+            }
+
+            /// Sets the thickness of the element's text decoration line
+            /// (`/TextDecorationThickness`).
+            pub fn with_text_decoration_thickness(mut self, value: f32) -> Self {
+                self.layout_attrs
+                    .push(internal::LayoutAttr::TextDecorationThickness(value));
+                self
+            }
+        }
+    };
+}
+
+/// A generic block- or inline-level structure element (e.g. `P`, `Span`, `Div`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PTag {
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+}
+
+impl PTag {
+    /// Creates a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+common_builders!(PTag);
+layout_builders!(PTag);
+
+/// A footnote or endnote (`Note`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteTag {
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+}
+
+impl NoteTag {
+    /// Creates a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+common_builders!(NoteTag);
+layout_builders!(NoteTag);
+
+/// A table (`Table`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableTag {
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+    pub(crate) table_attrs: Vec<internal::TableAttr>,
+}
+
+impl TableTag {
+    /// Creates a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a textual summary of the table's purpose and structure (`/Summary`).
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.table_attrs
+            .push(internal::TableAttr::Summary(summary.into()));
+        self
+    }
+}
+common_builders!(TableTag);
+layout_builders!(TableTag);
+
+/// A table row (`TR`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TRTag {
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+}
+
+impl TRTag {
+    /// Creates a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+common_builders!(TRTag);
+layout_builders!(TRTag);
+
+/// A table data cell (`TD`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TDTag {
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+    pub(crate) table_attrs: Vec<internal::TableAttr>,
+}
+
+impl TDTag {
+    /// Creates a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of rows/columns this cell spans (`/RowSpan`/`/ColSpan`).
+    pub fn with_span(mut self, span: TableCellSpan) -> Self {
+        self.table_attrs.push(internal::TableAttr::CellSpan(span));
+        self
+    }
+
+    /// Sets the headers this cell is explicitly associated with (`/Headers`),
+    /// overriding the automatic derivation described on `table_headers`.
+    pub fn with_headers(mut self, headers: SmallVec<[TagId; 1]>) -> Self {
+        self.table_attrs
+            .push(internal::TableAttr::CellHeaders(headers));
+        self
+    }
+}
+common_builders!(TDTag);
+layout_builders!(TDTag);
+
+/// A table header cell (`TH`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct THTag {
+    pub(crate) scope: TableHeaderScope,
+    pub(crate) attrs: Vec<internal::Attr>,
+    pub(crate) layout_attrs: Vec<internal::LayoutAttr>,
+    pub(crate) table_attrs: Vec<internal::TableAttr>,
+}
+
+impl THTag {
+    /// Creates a new tag with the given header scope.
+    pub fn new(scope: TableHeaderScope) -> Self {
+        Self {
+            scope,
+            attrs: Vec::new(),
+            layout_attrs: Vec::new(),
+            table_attrs: vec![internal::TableAttr::HeaderScope(scope)],
+        }
+    }
+
+    /// Sets the number of rows/columns this cell spans (`/RowSpan`/`/ColSpan`).
+    pub fn with_span(mut self, span: TableCellSpan) -> Self {
+        self.table_attrs.push(internal::TableAttr::CellSpan(span));
+        self
+    }
+
+    /// Sets the headers this cell is explicitly associated with (`/Headers`),
+    /// overriding the automatic derivation described on `table_headers`.
+    pub fn with_headers(mut self, headers: SmallVec<[TagId; 1]>) -> Self {
+        self.table_attrs
+            .push(internal::TableAttr::CellHeaders(headers));
+        self
+    }
+}
+common_builders!(THTag);
+layout_builders!(THTag);
+
+/// A structure element: one of the standard tags above, or a user-defined tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    /// See [`PTag`].
+    P(PTag),
+    /// See [`NoteTag`].
+    Note(NoteTag),
+    /// See [`TableTag`].
+    Table(TableTag),
+    /// See [`TRTag`].
+    TR(TRTag),
+    /// See [`TDTag`].
+    TD(TDTag),
+    /// See [`THTag`].
+    TH(THTag),
+    /// See [`CustomTag`].
+    Custom(CustomTag),
+}
+
+macro_rules! tag_from {
+    ($variant:ident, $ty:ty) => {
+        impl From<$ty> for Tag {
+            fn from(value: $ty) -> Self {
+                Tag::$variant(value)
+            }
+        }
+    };
+}
+tag_from!(P, PTag);
+tag_from!(Note, NoteTag);
+tag_from!(Table, TableTag);
+tag_from!(TR, TRTag);
+tag_from!(TD, TDTag);
+tag_from!(TH, THTag);
+tag_from!(Custom, CustomTag);
@@ -0,0 +1,57 @@
+//! The logical structure tree used to produce tagged PDF output.
+//!
+//! A [`TagTree`] is built out of [`TagGroup`]s, each wrapping one of the standard
+//! tags in [`tag`] together with its children. See [`tag`]'s module documentation
+//! for a full example.
+
+pub mod tag;
+mod serialize;
+
+use tag::Tag;
+
+/// A node of a [`TagTree`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagTreeNode {
+    /// A structure element together with its children.
+    Group(TagGroup),
+}
+
+/// One structure element together with its children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagGroup {
+    pub(crate) tag: Tag,
+    pub(crate) children: Vec<TagTreeNode>,
+}
+
+impl TagGroup {
+    /// Creates a new, childless group wrapping `tag`.
+    pub fn new(tag: impl Into<Tag>) -> Self {
+        Self {
+            tag: tag.into(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends a child group.
+    pub fn push(&mut self, child: TagGroup) {
+        self.children.push(TagTreeNode::Group(child));
+    }
+}
+
+/// The root of a document's logical structure tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagTree {
+    pub(crate) children: Vec<TagTreeNode>,
+}
+
+impl TagTree {
+    /// Creates an empty tag tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a top-level group.
+    pub fn push(&mut self, group: TagGroup) {
+        self.children.push(TagTreeNode::Group(group));
+    }
+}
@@ -0,0 +1,844 @@
+//! Converts a [`TagTree`] into the auxiliary data the structure tree root needs
+//! at serialization time: the `/IDTree`, resolved `/Ref` attributes, automatic
+//! table `/Headers`, and the `/RoleMap`/`/Namespaces` entries for custom tags.
+//!
+//! None of this writes `pdf_writer::Chunk` bytes directly: callers pass in a
+//! reference allocator and get back plain data, which the page/document
+//! serializer then writes alongside the rest of the structure tree.
+
+use std::collections::{HashMap, HashSet};
+
+use super::tag::internal::{Attr, TableAttr};
+use super::tag::{id_tree, table_headers, Tag, TagId, TableCellSpan, TableHeaderScope};
+use super::{TagGroup, TagTree, TagTreeNode};
+
+/// An error found while validating a [`TagTree`] before serialization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TaggingError {
+    /// An element's `Ref` attribute names an automatically-assigned `Note` id
+    /// (see [`TagId::auto_note`]) that doesn't belong to any `Note` structure
+    /// element actually present in the tree, so assistive technology following
+    /// the reference would have nowhere to go. See PDF/UA-1, ISO 14289-1 §7.9.
+    MissingNoteAncestor(TagId),
+}
+
+impl std::fmt::Display for TaggingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaggingError::MissingNoteAncestor(id) => write!(
+                f,
+                "Ref target {:?} does not resolve to a Note element in the tag tree",
+                id.as_bytes()
+            ),
+        }
+    }
+}
+
+fn attrs_of(tag: &Tag) -> &[Attr] {
+    match tag {
+        Tag::P(t) => &t.attrs,
+        Tag::Note(t) => &t.attrs,
+        Tag::Table(t) => &t.attrs,
+        Tag::TR(t) => &t.attrs,
+        Tag::TD(t) => &t.attrs,
+        Tag::TH(t) => &t.attrs,
+        // A custom tag carries no attrs of its own: `/RoleMap`/`/Namespaces`
+        // metadata is derived from `CustomTag` directly, see `collect_custom_roles`.
+        Tag::Custom(_) => &[],
+    }
+}
+
+/// Assigns an automatic id (see [`TagId::auto_note`]) to every `Note` element in
+/// `tree` that doesn't already have one.
+pub(crate) fn assign_note_ids(tree: &mut TagTree) {
+    let mut next = 0u32;
+    assign_note_ids_nodes(&mut tree.children, &mut next);
+}
+
+fn assign_note_ids_nodes(nodes: &mut [TagTreeNode], next: &mut u32) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        if let Tag::Note(note) = &mut group.tag {
+            if !note.attrs.iter().any(|attr| matches!(attr, Attr::Id(_))) {
+                note.attrs.push(Attr::Id(TagId::auto_note(*next)));
+                *next += 1;
+            }
+        }
+        assign_note_ids_nodes(&mut group.children, next);
+    }
+}
+
+/// Collects every `(id, reference)` pair needed for the `/IDTree`, by walking
+/// `tree` and calling `alloc` once for every group that carries an `Attr::Id`.
+/// In the real writer, `alloc` returns the same reference the group's structure
+/// element dictionary is written at.
+pub(crate) fn collect_ids(
+    tree: &TagTree,
+    alloc: &mut impl FnMut(&TagGroup) -> pdf_writer::Ref,
+) -> Vec<(TagId, pdf_writer::Ref)> {
+    let mut ids = Vec::new();
+    collect_ids_nodes(&tree.children, alloc, &mut ids);
+    ids
+}
+
+fn collect_ids_nodes(
+    nodes: &[TagTreeNode],
+    alloc: &mut impl FnMut(&TagGroup) -> pdf_writer::Ref,
+    ids: &mut Vec<(TagId, pdf_writer::Ref)>,
+) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        if let Some(id) = group_id(group) {
+            ids.push((id.clone(), alloc(group)));
+        }
+        collect_ids_nodes(&group.children, alloc, ids);
+    }
+}
+
+fn group_id(group: &TagGroup) -> Option<&TagId> {
+    attrs_of(&group.tag).iter().find_map(|attr| match attr {
+        Attr::Id(id) => Some(id),
+        _ => None,
+    })
+}
+
+/// Builds the structure tree root's `/IDTree`, first auto-assigning ids to every
+/// `Note` that doesn't have one. Returns `None` if the tree ends up with no ids
+/// at all, in which case `/IDTree` must be omitted entirely.
+pub(crate) fn build_id_tree(
+    tree: &mut TagTree,
+    alloc: &mut impl FnMut(&TagGroup) -> pdf_writer::Ref,
+) -> Option<id_tree::IdTreeNode> {
+    assign_note_ids(tree);
+    id_tree::build(collect_ids(tree, alloc))
+}
+
+/// Validates that every `Ref` attribute pointing at what looks like an
+/// automatically assigned `Note` id actually resolves to a `Note` present in the
+/// tree, per the PDF/UA-1 requirement that a footnote reference must always
+/// reach an identifiable note (ISO 14289-1, §7.9).
+pub(crate) fn validate_note_refs(tree: &TagTree) -> Result<(), TaggingError> {
+    let note_ids = collect_note_ids(tree);
+    validate_note_refs_nodes(&tree.children, &note_ids)
+}
+
+fn collect_note_ids(tree: &TagTree) -> HashSet<TagId> {
+    let mut ids = HashSet::new();
+    collect_note_ids_nodes(&tree.children, &mut ids);
+    ids
+}
+
+fn collect_note_ids_nodes(nodes: &[TagTreeNode], ids: &mut HashSet<TagId>) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        if let Tag::Note(note) = &group.tag {
+            if let Some(id) = note.attrs.iter().find_map(|attr| match attr {
+                Attr::Id(id) => Some(id.clone()),
+                _ => None,
+            }) {
+                ids.insert(id);
+            }
+        }
+        collect_note_ids_nodes(&group.children, ids);
+    }
+}
+
+fn validate_note_refs_nodes(
+    nodes: &[TagTreeNode],
+    note_ids: &HashSet<TagId>,
+) -> Result<(), TaggingError> {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        for attr in attrs_of(&group.tag) {
+            if let Attr::Ref(refs) = attr {
+                for id in refs {
+                    if id.as_bytes().first() == Some(&b'N') && !note_ids.contains(id) {
+                        return Err(TaggingError::MissingNoteAncestor(id.clone()));
+                    }
+                }
+            }
+        }
+        validate_note_refs_nodes(&group.children, note_ids)?;
+    }
+    Ok(())
+}
+
+/// Resolves every `Ref` attribute in `tree` to the indirect reference of the
+/// structure element it names, using the same `(id, reference)` pairs as the
+/// `/IDTree` (see [`collect_ids`]). `alloc` is called once per referring
+/// element, with the same semantics as in [`collect_ids`]: it must return the
+/// reference the element's own structure element dictionary is written at, so
+/// the caller can attach the resolved `/Ref` array there. Ids that don't
+/// resolve to any element in the tree are dropped from the result; for notes,
+/// [`validate_note_refs`] is what rejects those before this point.
+pub(crate) fn resolve_refs(
+    tree: &TagTree,
+    ids: &[(TagId, pdf_writer::Ref)],
+    alloc: &mut impl FnMut(&TagGroup) -> pdf_writer::Ref,
+) -> Vec<(pdf_writer::Ref, Vec<pdf_writer::Ref>)> {
+    let index: HashMap<&TagId, pdf_writer::Ref> =
+        ids.iter().map(|(id, reference)| (id, *reference)).collect();
+    let mut resolved = Vec::new();
+    resolve_refs_nodes(&tree.children, &index, alloc, &mut resolved);
+    resolved
+}
+
+fn resolve_refs_nodes(
+    nodes: &[TagTreeNode],
+    index: &HashMap<&TagId, pdf_writer::Ref>,
+    alloc: &mut impl FnMut(&TagGroup) -> pdf_writer::Ref,
+    resolved: &mut Vec<(pdf_writer::Ref, Vec<pdf_writer::Ref>)>,
+) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        for attr in attrs_of(&group.tag) {
+            if let Attr::Ref(refs) = attr {
+                let targets: Vec<pdf_writer::Ref> = refs
+                    .iter()
+                    .filter_map(|id| index.get(id).copied())
+                    .collect();
+                if !targets.is_empty() {
+                    resolved.push((alloc(group), targets));
+                }
+            }
+        }
+        resolve_refs_nodes(&group.children, index, alloc, resolved);
+    }
+}
+
+fn cell_span(attrs: &[TableAttr]) -> TableCellSpan {
+    attrs
+        .iter()
+        .find_map(|attr| match attr {
+            TableAttr::CellSpan(span) => Some(*span),
+            _ => None,
+        })
+        .unwrap_or(TableCellSpan::ONE)
+}
+
+fn cell_headers(attrs: &[TableAttr]) -> Option<smallvec::SmallVec<[TagId; 1]>> {
+    attrs.iter().find_map(|attr| match attr {
+        TableAttr::CellHeaders(headers) => Some(headers.clone()),
+        _ => None,
+    })
+}
+
+fn cell_id(attrs: &[Attr]) -> Option<TagId> {
+    attrs.iter().find_map(|attr| match attr {
+        Attr::Id(id) => Some(id.clone()),
+        _ => None,
+    })
+}
+
+/// The info needed to place one table cell into the expanded grid, read from a
+/// `TH`/`TD` tag. `None` for anything else (rows themselves don't occupy a cell).
+fn cell_info(
+    tag: &Tag,
+) -> Option<(
+    Option<TableHeaderScope>,
+    TableCellSpan,
+    Option<smallvec::SmallVec<[TagId; 1]>>,
+    Option<TagId>,
+)> {
+    match tag {
+        Tag::TH(th) => Some((
+            Some(th.scope),
+            cell_span(&th.table_attrs),
+            cell_headers(&th.table_attrs),
+            cell_id(&th.attrs),
+        )),
+        Tag::TD(td) => Some((
+            None,
+            cell_span(&td.table_attrs),
+            cell_headers(&td.table_attrs),
+            cell_id(&td.attrs),
+        )),
+        _ => None,
+    }
+}
+
+fn ensure_len(row: &mut Vec<Option<usize>>, len: usize) {
+    if row.len() < len {
+        row.resize(len, None);
+    }
+}
+
+/// One `/RoleMap` entry for a [`super::tag::CustomTag`], and, if the tag isn't
+/// in the default structure namespace, the `/Namespaces` entry needed to
+/// disambiguate it for PDF 2.0 readers (ISO 32000-2, §14.7.4.4).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CustomRole {
+    pub(crate) name: String,
+    pub(crate) namespace: Option<String>,
+    pub(crate) standard_type: pdf_writer::types::StructRole,
+}
+
+/// Collects one [`CustomRole`] per distinct `CustomTag` used in `tree`,
+/// deduplicated by [`super::tag::CustomTag::key`] so that two otherwise
+/// identical custom tags don't produce two `/RoleMap`/`/Namespaces` entries.
+pub(crate) fn collect_custom_roles(tree: &TagTree) -> Vec<CustomRole> {
+    let mut seen = HashSet::new();
+    let mut roles = Vec::new();
+    collect_custom_roles_nodes(&tree.children, &mut seen, &mut roles);
+    roles
+}
+
+fn collect_custom_roles_nodes<'a>(
+    nodes: &'a [TagTreeNode],
+    seen: &mut HashSet<(&'a str, Option<&'a str>)>,
+    roles: &mut Vec<CustomRole>,
+) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        if let Tag::Custom(custom) = &group.tag {
+            if seen.insert(custom.key()) {
+                roles.push(CustomRole {
+                    name: custom.name.clone(),
+                    namespace: custom.namespace.clone(),
+                    standard_type: custom.standard_type.clone(),
+                });
+            }
+        }
+        collect_custom_roles_nodes(&group.children, seen, roles);
+    }
+}
+
+/// Runs the opt-in automatic `/Headers` pass (see `tag::table_headers`) over
+/// every `Table` in `tree`, mutating `TD`/`TH` cells in place. Cells that
+/// already have an explicit `/Headers` value are left untouched.
+pub(crate) fn apply_automatic_table_headers(tree: &mut TagTree) {
+    apply_automatic_table_headers_nodes(&mut tree.children);
+}
+
+fn apply_automatic_table_headers_nodes(nodes: &mut [TagTreeNode]) {
+    for node in nodes {
+        let TagTreeNode::Group(group) = node;
+        if matches!(group.tag, Tag::Table(_)) {
+            apply_to_table(group);
+        }
+        apply_automatic_table_headers_nodes(&mut group.children);
+    }
+}
+
+fn apply_to_table(table: &mut TagGroup) {
+    let mut cells = Vec::new();
+    let mut locations = Vec::new();
+    let mut id_generated = Vec::new();
+    let mut grid: table_headers::Grid = Vec::new();
+    // Column -> (owning cell index, rows still left to carry after this one).
+    let mut carry: HashMap<usize, (usize, u32)> = HashMap::new();
+    let mut next_auto = 0u32;
+
+    for (row_idx, row_node) in table.children.iter().enumerate() {
+        let TagTreeNode::Group(row_group) = row_node;
+        let mut row: Vec<Option<usize>> = Vec::new();
+        let mut col = 0usize;
+        // Columns (re-)inserted into `carry` while processing *this* row - a
+        // rowspan that starts here must survive this row untouched; it's only
+        // consumed (and its count decremented) starting the row after.
+        let mut fresh_carry: HashSet<usize> = HashSet::new();
+
+        for (cell_idx, cell_node) in row_group.children.iter().enumerate() {
+            while carry.contains_key(&col) {
+                ensure_len(&mut row, col + 1);
+                row[col] = Some(carry[&col].0);
+                col += 1;
+            }
+
+            let TagTreeNode::Group(cell_group) = cell_node;
+            let Some((scope, span, explicit_headers, existing_id)) = cell_info(&cell_group.tag)
+            else {
+                continue;
+            };
+
+            // Only headers are ever looked up by another cell's `/Headers`
+            // (see `table_headers::headers_for`), so a plain data cell with no
+            // id of its own doesn't need one attached to its tag - doing so
+            // would only bloat `/IDTree` with an entry nothing points at.
+            let generated = scope.is_some() && existing_id.is_none();
+            let id = existing_id.unwrap_or_else(|| {
+                let id = TagId::auto_header(next_auto);
+                next_auto += 1;
+                id
+            });
+
+            let global_idx = cells.len();
+            let cols = span.col_span().map_or(1, |c| c.get() as usize);
+            let rows = span.row_span().map_or(1, |r| r.get());
+
+            for c in col..col + cols {
+                ensure_len(&mut row, c + 1);
+                row[c] = Some(global_idx);
+                if rows > 1 {
+                    carry.insert(c, (global_idx, rows - 1));
+                    fresh_carry.insert(c);
+                }
+            }
+            col += cols;
+
+            cells.push(table_headers::HeaderCell {
+                id,
+                scope,
+                explicit_headers,
+            });
+            locations.push((row_idx, cell_idx));
+            id_generated.push(generated);
+        }
+
+        if let Some(&max_col) = carry.keys().max() {
+            while col <= max_col {
+                if let Some(&(owner, _)) = carry.get(&col) {
+                    ensure_len(&mut row, col + 1);
+                    row[col] = Some(owner);
+                }
+                col += 1;
+            }
+        }
+
+        grid.push(row);
+
+        carry.retain(|col, value| {
+            if fresh_carry.contains(col) {
+                return true;
+            }
+            value.1 -= 1;
+            value.1 > 0
+        });
+    }
+
+    let computed = table_headers::compute(&grid, &cells);
+
+    for (idx, headers) in computed.into_iter().enumerate() {
+        let (row_idx, cell_idx) = locations[idx];
+        let TagTreeNode::Group(row_group) = &mut table.children[row_idx];
+        let TagTreeNode::Group(cell_group) = &mut row_group.children[cell_idx];
+
+        if id_generated[idx] {
+            let id = cells[idx].id.clone();
+            match &mut cell_group.tag {
+                Tag::TH(th) => th.attrs.push(Attr::Id(id)),
+                Tag::TD(td) => td.attrs.push(Attr::Id(id)),
+                _ => {}
+            }
+        }
+
+        if let Some(headers) = headers {
+            match &mut cell_group.tag {
+                Tag::TH(th) => th.table_attrs.push(TableAttr::CellHeaders(headers)),
+                Tag::TD(td) => td.table_attrs.push(TableAttr::CellHeaders(headers)),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interchange::tagging::tag::NoteTag;
+
+    fn alloc(counter: &mut i32) -> impl FnMut(&TagGroup) -> pdf_writer::Ref + '_ {
+        move |_| {
+            *counter += 1;
+            pdf_writer::Ref::new(*counter)
+        }
+    }
+
+    #[test]
+    fn note_without_id_gets_one_assigned() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(NoteTag::new()));
+
+        let mut counter = 0;
+        let ids = build_id_tree(&mut tree, &mut alloc(&mut counter));
+
+        assert!(ids.is_some());
+        let TagTreeNode::Group(group) = &tree.children[0];
+        let Tag::Note(note) = &group.tag else {
+            unreachable!()
+        };
+        assert!(note.attrs.iter().any(|a| matches!(a, Attr::Id(_))));
+    }
+
+    #[test]
+    fn note_with_explicit_id_is_left_untouched() {
+        let id = TagId::from(*b"explicit");
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(NoteTag::new().with_id(id.clone())));
+
+        assign_note_ids(&mut tree);
+
+        let TagTreeNode::Group(group) = &tree.children[0];
+        let Tag::Note(note) = &group.tag else {
+            unreachable!()
+        };
+        let ids: Vec<_> = note
+            .attrs
+            .iter()
+            .filter_map(|a| match a {
+                Attr::Id(id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec![id]);
+    }
+
+    #[test]
+    fn empty_tree_has_no_id_tree() {
+        let mut tree = TagTree::new();
+        let mut counter = 0;
+        assert!(build_id_tree(&mut tree, &mut alloc(&mut counter)).is_none());
+    }
+
+    // Builds a `P` tag whose only attribute is a `Ref` to `id`, pushed onto
+    // `attrs` directly rather than through `with_refs`.
+    fn p_tag_with_ref(id: TagId) -> super::super::tag::PTag {
+        let mut p = super::super::tag::PTag::new();
+        p.attrs.push(Attr::Ref(smallvec::smallvec![id]));
+        p
+    }
+
+    #[test]
+    fn ref_to_missing_note_is_rejected() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(p_tag_with_ref(TagId::auto(b'N', 0))));
+        assert_eq!(
+            validate_note_refs(&tree),
+            Err(TaggingError::MissingNoteAncestor(TagId::auto(b'N', 0)))
+        );
+    }
+
+    #[test]
+    fn ref_to_present_note_is_accepted() {
+        let note_id = TagId::auto(b'N', 0);
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(NoteTag::new().with_id(note_id.clone())));
+        tree.push(TagGroup::new(p_tag_with_ref(note_id)));
+        assert_eq!(validate_note_refs(&tree), Ok(()));
+    }
+
+    #[test]
+    fn with_refs_resolves_to_the_targets_allocated_reference() {
+        let note_id = TagId::auto(b'N', 0);
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(NoteTag::new().with_id(note_id.clone())));
+        tree.push(TagGroup::new(
+            super::super::tag::PTag::new().with_refs(smallvec::smallvec![note_id]),
+        ));
+
+        let mut counter = 0;
+        let ids = collect_ids(&tree, &mut alloc(&mut counter));
+        let note_ref = ids[0].1;
+
+        let mut resolve_counter = 0;
+        let resolved = resolve_refs(&tree, &ids, &mut alloc(&mut resolve_counter));
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].1, vec![note_ref]);
+    }
+
+    #[test]
+    fn refs_to_unresolvable_ids_are_dropped() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(
+            super::super::tag::PTag::new().with_refs(smallvec::smallvec![TagId::auto(b'N', 0)]),
+        ));
+
+        let ids = Vec::new();
+        let mut counter = 0;
+        let resolved = resolve_refs(&tree, &ids, &mut alloc(&mut counter));
+        assert!(resolved.is_empty());
+    }
+
+    use super::super::tag::{TDTag, THTag, TRTag, TableTag};
+
+    fn headers_of(tree: &TagTree, row: usize, cell: usize) -> Option<Vec<TagId>> {
+        let TagTreeNode::Group(table) = &tree.children[0];
+        let TagTreeNode::Group(row_group) = &table.children[row];
+        let TagTreeNode::Group(cell_group) = &row_group.children[cell];
+        let table_attrs = match &cell_group.tag {
+            Tag::TH(th) => &th.table_attrs,
+            Tag::TD(td) => &td.table_attrs,
+            _ => unreachable!(),
+        };
+        cell_headers(table_attrs).map(|headers| headers.into_iter().collect())
+    }
+
+    #[test]
+    fn row_scope_header_is_associated_with_the_rest_of_its_row() {
+        // | TH Row "name" | TD "Alice" | TD "Bob" |
+        let mut table = TagGroup::new(TableTag::new());
+        let mut row = TagGroup::new(TRTag::new());
+        row.push(TagGroup::new(THTag::new(TableHeaderScope::Row)));
+        row.push(TagGroup::new(TDTag::new()));
+        row.push(TagGroup::new(TDTag::new()));
+        table.push(row);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let header_id = {
+            let TagTreeNode::Group(table) = &tree.children[0];
+            let TagTreeNode::Group(row_group) = &table.children[0];
+            let TagTreeNode::Group(th_group) = &row_group.children[0];
+            let Tag::TH(th) = &th_group.tag else {
+                unreachable!()
+            };
+            cell_id(&th.attrs).unwrap()
+        };
+
+        assert_eq!(headers_of(&tree, 0, 1), Some(vec![header_id.clone()]));
+        assert_eq!(headers_of(&tree, 0, 2), Some(vec![header_id]));
+        assert_eq!(headers_of(&tree, 0, 0), None);
+    }
+
+    #[test]
+    fn column_scope_header_is_associated_with_the_rest_of_its_column() {
+        // Row 0: TH Column "Name"
+        // Row 1: TD "Alice"
+        // Row 2: TD "Bob"
+        let mut table = TagGroup::new(TableTag::new());
+        let mut header_row = TagGroup::new(TRTag::new());
+        header_row.push(TagGroup::new(THTag::new(TableHeaderScope::Column)));
+        table.push(header_row);
+        for _ in 0..2 {
+            let mut row = TagGroup::new(TRTag::new());
+            row.push(TagGroup::new(TDTag::new()));
+            table.push(row);
+        }
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let header_id = {
+            let TagTreeNode::Group(table) = &tree.children[0];
+            let TagTreeNode::Group(row_group) = &table.children[0];
+            let TagTreeNode::Group(th_group) = &row_group.children[0];
+            let Tag::TH(th) = &th_group.tag else {
+                unreachable!()
+            };
+            cell_id(&th.attrs).unwrap()
+        };
+
+        assert_eq!(headers_of(&tree, 1, 0), Some(vec![header_id.clone()]));
+        assert_eq!(headers_of(&tree, 2, 0), Some(vec![header_id]));
+    }
+
+    #[test]
+    fn both_scope_header_is_associated_with_its_row_and_its_column() {
+        // |   TH Both "Totals" | TD "Q1" | TD "Q2" |
+        // | TD "Revenue"       | TD 10   | TD 20   |
+        let mut table = TagGroup::new(TableTag::new());
+
+        let mut row0 = TagGroup::new(TRTag::new());
+        row0.push(TagGroup::new(THTag::new(TableHeaderScope::Both)));
+        row0.push(TagGroup::new(TDTag::new()));
+        row0.push(TagGroup::new(TDTag::new()));
+        table.push(row0);
+
+        let mut row1 = TagGroup::new(TRTag::new());
+        row1.push(TagGroup::new(TDTag::new()));
+        row1.push(TagGroup::new(TDTag::new()));
+        row1.push(TagGroup::new(TDTag::new()));
+        table.push(row1);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let header_id = {
+            let TagTreeNode::Group(table) = &tree.children[0];
+            let TagTreeNode::Group(row_group) = &table.children[0];
+            let TagTreeNode::Group(th_group) = &row_group.children[0];
+            let Tag::TH(th) = &th_group.tag else {
+                unreachable!()
+            };
+            cell_id(&th.attrs).unwrap()
+        };
+
+        // Shares a row with the Both header.
+        assert_eq!(headers_of(&tree, 0, 1), Some(vec![header_id.clone()]));
+        // Shares a column with the Both header.
+        assert_eq!(headers_of(&tree, 1, 0), Some(vec![header_id.clone()]));
+        // Shares neither: no automatic headers.
+        assert_eq!(headers_of(&tree, 1, 1), None);
+    }
+
+    #[test]
+    fn transitive_headers_flow_through_a_headers_own_explicit_headers() {
+        // A row header whose own `/Headers` was set explicitly to point at a
+        // section header: the data cell sharing its row should pick up both.
+        let section_id = TagId::from(*b"section1");
+        let mut table = TagGroup::new(TableTag::new());
+        let mut row = TagGroup::new(TRTag::new());
+        row.push(TagGroup::new(
+            THTag::new(TableHeaderScope::Row)
+                .with_id(TagId::from(*b"rowhead1"))
+                .with_headers(smallvec::smallvec![section_id.clone()]),
+        ));
+        row.push(TagGroup::new(TDTag::new()));
+        table.push(row);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let mut headers = headers_of(&tree, 0, 1).unwrap();
+        headers.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        let mut expected = vec![TagId::from(*b"rowhead1"), section_id];
+        expected.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        assert_eq!(headers, expected);
+    }
+
+    #[test]
+    fn explicit_headers_on_a_data_cell_are_not_overwritten() {
+        let explicit_id = TagId::from(*b"explicit");
+        let mut table = TagGroup::new(TableTag::new());
+        let mut row = TagGroup::new(TRTag::new());
+        row.push(TagGroup::new(THTag::new(TableHeaderScope::Row)));
+        row.push(TagGroup::new(
+            TDTag::new().with_headers(smallvec::smallvec![explicit_id.clone()]),
+        ));
+        table.push(row);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        assert_eq!(headers_of(&tree, 0, 1), Some(vec![explicit_id]));
+    }
+
+    #[test]
+    fn row_spanning_header_carries_over_to_every_row_it_spans() {
+        // | TH Column rowspan=3 | TD "unrelated" |   <- row 0
+        // | <covered>           | TD             |   <- row 1 (ragged: omits the covered cell)
+        // | <covered>           | TD             |   <- row 2 (ragged: omits the covered cell)
+        // | TD (fresh, no longer covered)         |   <- row 3
+        let mut table = TagGroup::new(TableTag::new());
+
+        let mut row0 = TagGroup::new(TRTag::new());
+        row0.push(TagGroup::new(
+            THTag::new(TableHeaderScope::Column)
+                .with_span(TableCellSpan::row(std::num::NonZeroU32::new(3).unwrap())),
+        ));
+        row0.push(TagGroup::new(TDTag::new()));
+        table.push(row0);
+
+        let mut row1 = TagGroup::new(TRTag::new());
+        row1.push(TagGroup::new(TDTag::new()));
+        table.push(row1);
+
+        let mut row2 = TagGroup::new(TRTag::new());
+        row2.push(TagGroup::new(TDTag::new()));
+        table.push(row2);
+
+        let mut row3 = TagGroup::new(TRTag::new());
+        row3.push(TagGroup::new(TDTag::new()));
+        row3.push(TagGroup::new(TDTag::new()));
+        table.push(row3);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let header_id = {
+            let TagTreeNode::Group(table) = &tree.children[0];
+            let TagTreeNode::Group(row_group) = &table.children[0];
+            let TagTreeNode::Group(th_group) = &row_group.children[0];
+            let Tag::TH(th) = &th_group.tag else {
+                unreachable!()
+            };
+            cell_id(&th.attrs).unwrap()
+        };
+
+        // The ragged rows' only real cell is column 1, not column 0 - if the
+        // rowspan carry were dropped early (or a stale entry shifted the real
+        // cell into column 0), this would incorrectly come back `Some`.
+        assert_eq!(headers_of(&tree, 1, 0), None);
+        assert_eq!(headers_of(&tree, 2, 0), None);
+        // Column 0 is genuinely free again by row 3: a fresh cell there picks
+        // up the column header like any other column-0 cell would.
+        assert_eq!(headers_of(&tree, 3, 0), Some(vec![header_id]));
+        // The unrelated row-0 cell never shared the header's column.
+        assert_eq!(headers_of(&tree, 0, 1), None);
+    }
+
+    #[test]
+    fn data_cells_without_an_explicit_id_do_not_get_one_generated() {
+        let mut table = TagGroup::new(TableTag::new());
+        let mut row = TagGroup::new(TRTag::new());
+        row.push(TagGroup::new(THTag::new(TableHeaderScope::Row)));
+        row.push(TagGroup::new(TDTag::new()));
+        table.push(row);
+
+        let mut tree = TagTree::new();
+        tree.push(table);
+        apply_automatic_table_headers(&mut tree);
+
+        let TagTreeNode::Group(table) = &tree.children[0];
+        let TagTreeNode::Group(row_group) = &table.children[0];
+        let TagTreeNode::Group(td_group) = &row_group.children[1];
+        let Tag::TD(td) = &td_group.tag else {
+            unreachable!()
+        };
+        assert!(!td.attrs.iter().any(|a| matches!(a, Attr::Id(_))));
+    }
+
+    use super::super::tag::CustomTag;
+
+    #[test]
+    fn custom_tag_can_be_wrapped_in_a_tag_group() {
+        let tag = CustomTag::new("mrow", pdf_writer::types::StructRole::Div);
+        let group = TagGroup::new(tag);
+        assert!(matches!(group.tag, Tag::Custom(_)));
+    }
+
+    #[test]
+    fn custom_roles_are_collected_with_their_namespace() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(
+            CustomTag::new("mrow", pdf_writer::types::StructRole::Div)
+                .with_namespace("http://www.w3.org/1998/Math/MathML"),
+        ));
+
+        let roles = collect_custom_roles(&tree);
+        assert_eq!(roles.len(), 1);
+        assert_eq!(roles[0].name, "mrow");
+        assert_eq!(
+            roles[0].namespace.as_deref(),
+            Some("http://www.w3.org/1998/Math/MathML")
+        );
+    }
+
+    #[test]
+    fn identical_custom_tags_produce_only_one_role() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(CustomTag::new(
+            "mrow",
+            pdf_writer::types::StructRole::Div,
+        )));
+        tree.push(TagGroup::new(CustomTag::new(
+            "mrow",
+            pdf_writer::types::StructRole::Div,
+        )));
+
+        assert_eq!(collect_custom_roles(&tree).len(), 1);
+    }
+
+    #[test]
+    fn same_name_in_different_namespaces_produces_two_roles() {
+        let mut tree = TagTree::new();
+        tree.push(TagGroup::new(CustomTag::new(
+            "title",
+            pdf_writer::types::StructRole::Div,
+        )));
+        tree.push(TagGroup::new(
+            CustomTag::new("title", pdf_writer::types::StructRole::Div)
+                .with_namespace("http://www.w3.org/1998/Math/MathML"),
+        ));
+
+        assert_eq!(collect_custom_roles(&tree).len(), 2);
+    }
+}
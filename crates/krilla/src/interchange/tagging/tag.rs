@@ -48,6 +48,86 @@ impl TagId {
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_slice()
     }
+
+    /// Creates an automatically assigned id with the given reserved prefix byte.
+    ///
+    /// The prefix must never be `U`, since that's reserved for ids built through
+    /// [`TagId::from`] (see its doc comment), so automatically assigned ids can
+    /// never collide with user-provided ones.
+    pub(crate) fn auto(prefix: u8, index: u32) -> Self {
+        debug_assert_ne!(prefix, b'U');
+        let mut bytes: SmallVec<[u8; 16]> = SmallVec::new();
+        bytes.push(prefix);
+        bytes.extend(index.to_be_bytes());
+        TagId(bytes)
+    }
+
+    /// Creates an id for a `Note` element that the user didn't assign one to.
+    ///
+    /// PDF/UA-1 (ISO 14289-1, §7.9) requires every `Note` structure element to carry
+    /// an element identifier, so notes without a user-provided [`TagId`] still need
+    /// one.
+    pub(crate) fn auto_note(index: u32) -> Self {
+        Self::auto(b'N', index)
+    }
+
+    /// Creates an id for a table header cell that the user didn't assign one to,
+    /// so the automatic `/Headers` pass (see `table_headers`) has something to
+    /// point data cells at.
+    pub(crate) fn auto_header(index: u32) -> Self {
+        Self::auto(b'H', index)
+    }
+}
+
+/// A user-defined structure type that isn't one of the standard tags generated
+/// into `generated.rs`.
+///
+/// Custom tags let callers introduce domain-specific element types, such as a
+/// MathML `mrow` or a semantic `Definition`, while still producing a document
+/// that degrades gracefully for readers that only understand the standard
+/// structure types. They are accepted anywhere a generated tag is, via
+/// `TagGroup::new`.
+///
+/// During serialization, every distinct `(name, namespace)` pair is rolemapped in
+/// the structure tree root's `/RoleMap` to [`CustomTag::standard_type`]. On PDF 2.0
+/// documents, tags that set [`CustomTag::namespace`] are additionally recorded in
+/// the structure tree root's `/Namespaces` array, so that the same name can be
+/// rolemapped differently across namespaces.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CustomTag {
+    /// The tag's name, e.g. `"mrow"`.
+    pub name: String,
+    /// The URI of the namespace the tag is defined in, e.g.
+    /// `"http://www.w3.org/1998/Math/MathML"`. `None` places the tag in the
+    /// default structure namespace, which is always available, even for PDF
+    /// versions below 2.0.
+    pub namespace: Option<String>,
+    /// The standard structure type this tag is rolemapped to, so that readers
+    /// without knowledge of `namespace` can still make sense of the content.
+    pub standard_type: pdf_writer::types::StructRole,
+}
+
+impl CustomTag {
+    /// Creates a new custom tag in the default structure namespace.
+    pub fn new(name: impl Into<String>, standard_type: pdf_writer::types::StructRole) -> Self {
+        Self {
+            name: name.into(),
+            namespace: None,
+            standard_type,
+        }
+    }
+
+    /// Sets the namespace the tag is defined in.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// The key used to de-duplicate `/RoleMap` and `/Namespaces` entries for
+    /// otherwise-identical custom tags.
+    pub(crate) fn key(&self) -> (&str, Option<&str>) {
+        (self.name.as_str(), self.namespace.as_deref())
+    }
 }
 
 /// The list numbering type.
@@ -241,9 +321,205 @@ impl WritingMode {
     }
 }
 
+/// A value that applies independently to the before, after, start, and end edges
+/// of a layout element, or uniformly to all four.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Sides<T> {
+    /// The value for the before edge.
+    pub before: T,
+    /// The value for the after edge.
+    pub after: T,
+    /// The value for the start edge.
+    pub start: T,
+    /// The value for the end edge.
+    pub end: T,
+}
+
+impl<T: Copy> Sides<T> {
+    /// Creates a value that applies uniformly to all four edges.
+    pub const fn all(value: T) -> Self {
+        Self {
+            before: value,
+            after: value,
+            start: value,
+            end: value,
+        }
+    }
+}
+
+/// The style of the border drawn around a layout element's allocation rectangle.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum BorderStyle {
+    /// No border.
+    #[default]
+    None,
+    /// A border that is not rendered.
+    Hidden,
+    /// A series of round dots.
+    Dotted,
+    /// A series of short line segments.
+    Dashed,
+    /// A single solid line.
+    Solid,
+    /// Two parallel solid lines, the combined width of which (together with the
+    /// space between them) equals `BorderThickness`.
+    Double,
+    /// A 3D grooved border that looks as though it were carved into the page.
+    Groove,
+    /// A 3D ridged border, the opposite of `Groove`.
+    Ridge,
+    /// A 3D border that makes the element appear embedded in the page.
+    Inset,
+    /// A 3D border that makes the element appear embossed above the page.
+    Outset,
+}
+
+impl BorderStyle {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::BorderStyle {
+        match self {
+            BorderStyle::None => pdf_writer::types::BorderStyle::None,
+            BorderStyle::Hidden => pdf_writer::types::BorderStyle::Hidden,
+            BorderStyle::Dotted => pdf_writer::types::BorderStyle::Dotted,
+            BorderStyle::Dashed => pdf_writer::types::BorderStyle::Dashed,
+            BorderStyle::Solid => pdf_writer::types::BorderStyle::Solid,
+            BorderStyle::Double => pdf_writer::types::BorderStyle::Double,
+            BorderStyle::Groove => pdf_writer::types::BorderStyle::Groove,
+            BorderStyle::Ridge => pdf_writer::types::BorderStyle::Ridge,
+            BorderStyle::Inset => pdf_writer::types::BorderStyle::Inset,
+            BorderStyle::Outset => pdf_writer::types::BorderStyle::Outset,
+        }
+    }
+}
+
+/// The alignment, in the inline-progression direction, of text and other content
+/// within the lines of a BLSE.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum TextAlign {
+    /// Aligned with the start edge.
+    #[default]
+    Start,
+    /// Centered between the start and end edges.
+    Center,
+    /// Aligned with the end edge.
+    End,
+    /// Aligned with both the start and end edges, with extra space distributed
+    /// between glyphs or words as necessary.
+    Justify,
+}
+
+impl TextAlign {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::TextAlign {
+        match self {
+            TextAlign::Start => pdf_writer::types::TextAlign::Start,
+            TextAlign::Center => pdf_writer::types::TextAlign::Center,
+            TextAlign::End => pdf_writer::types::TextAlign::End,
+            TextAlign::Justify => pdf_writer::types::TextAlign::Justify,
+        }
+    }
+}
+
+/// The alignment, in the block-progression direction, of a BLSE's content within
+/// the element's allocation rectangle.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum BlockAlign {
+    /// Aligned with the before edge.
+    #[default]
+    Before,
+    /// Centered between the before and after edges.
+    Middle,
+    /// Aligned with the after edge.
+    After,
+    /// Aligned with both the before and after edges, with extra space distributed
+    /// between the element's children as necessary.
+    Justify,
+}
+
+impl BlockAlign {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::BlockAlign {
+        match self {
+            BlockAlign::Before => pdf_writer::types::BlockAlign::Before,
+            BlockAlign::Middle => pdf_writer::types::BlockAlign::Middle,
+            BlockAlign::After => pdf_writer::types::BlockAlign::After,
+            BlockAlign::Justify => pdf_writer::types::BlockAlign::Justify,
+        }
+    }
+}
+
+/// The alignment, in the inline-progression direction, of an ILSE within the
+/// available space of the line it appears on.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum InlineAlign {
+    /// Aligned with the start edge.
+    #[default]
+    Start,
+    /// Centered between the start and end edges.
+    Center,
+    /// Aligned with the end edge.
+    End,
+}
+
+impl InlineAlign {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::InlineAlign {
+        match self {
+            InlineAlign::Start => pdf_writer::types::InlineAlign::Start,
+            InlineAlign::Center => pdf_writer::types::InlineAlign::Center,
+            InlineAlign::End => pdf_writer::types::InlineAlign::End,
+        }
+    }
+}
+
+/// The height of each line in a BLSE, measured baseline to baseline.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum LineHeight {
+    /// A height chosen automatically based on the text's properties.
+    #[default]
+    Normal,
+    /// A height chosen automatically to make the lines fit as closely as
+    /// possible, without any extra space.
+    Auto,
+    /// An explicit height, in text space units.
+    Custom(f32),
+}
+
+impl LineHeight {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::LineHeight {
+        match self {
+            LineHeight::Normal => pdf_writer::types::LineHeight::Normal,
+            LineHeight::Auto => pdf_writer::types::LineHeight::Auto,
+            LineHeight::Custom(value) => pdf_writer::types::LineHeight::Custom(value),
+        }
+    }
+}
+
+/// The kind of text decoration line drawn across an ILSE's text.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum TextDecorationType {
+    /// No decoration line.
+    #[default]
+    None,
+    /// A line beneath the text.
+    Underline,
+    /// A line above the text.
+    Overline,
+    /// A line through the middle of the text.
+    LineThrough,
+}
+
+impl TextDecorationType {
+    pub(crate) fn to_pdf(self) -> pdf_writer::types::TextDecorationType {
+        match self {
+            TextDecorationType::None => pdf_writer::types::TextDecorationType::None,
+            TextDecorationType::Underline => pdf_writer::types::TextDecorationType::Underline,
+            TextDecorationType::Overline => pdf_writer::types::TextDecorationType::Overline,
+            TextDecorationType::LineThrough => pdf_writer::types::TextDecorationType::LineThrough,
+        }
+    }
+}
+
 // Internal attribute types that are used within the crate for compatibility with existing code
 pub(crate) mod internal {
     use super::*;
+    use crate::color::Color;
 
     /// An ordered set using binary search to find and insert items.
     #[derive(Clone, Debug, PartialEq)]
@@ -274,6 +550,7 @@ pub(crate) mod internal {
         Expanded(String),
         ActualText(String),
         HeadingLevel(NonZeroU32),
+        Ref(SmallVec<[TagId; 1]>),
     }
 
     #[derive(Clone, Debug, PartialEq)]
@@ -296,5 +573,307 @@ pub(crate) mod internal {
         BBox(Rect),
         Width(f32),
         Height(f32),
+        BackgroundColor(Color),
+        BorderColor(Sides<Color>),
+        BorderStyle(Sides<BorderStyle>),
+        BorderThickness(Sides<f32>),
+        Padding(Sides<f32>),
+        Color(Color),
+        SpaceBefore(f32),
+        SpaceAfter(f32),
+        StartIndent(f32),
+        EndIndent(f32),
+        TextIndent(f32),
+        TextAlign(TextAlign),
+        BlockAlign(BlockAlign),
+        InlineAlign(InlineAlign),
+        BaselineShift(f32),
+        LineHeight(LineHeight),
+        TextDecorationType(TextDecorationType),
+        TextDecorationColor(Color),
+        TextDecorationThickness(f32),
+    }
+}
+
+/// Support for the structure tree root's `/IDTree` entry.
+///
+/// PDF/UA-1 (ISO 14289-1, §7.9) requires every `Note` structure element to carry a
+/// unique [`TagId`], and ISO 32000-2, 14.7.4.3 requires the structure tree root to
+/// expose an `/IDTree` name tree whenever any structure element has one. Name trees
+/// must be sorted lexicographically by key and partitioned into `/Kids` nodes, each
+/// annotated with a `/Limits` pair giving its first and last key.
+pub(crate) mod id_tree {
+    use super::TagId;
+
+    /// Maximum number of entries per leaf node, keeping each leaf's `/Names` array a
+    /// reasonable size instead of emitting one giant leaf for large documents.
+    const LEAF_SIZE: usize = 64;
+
+    /// A node of a balanced name tree.
+    pub(crate) enum IdTreeNode {
+        /// A leaf node holding id-to-reference pairs, sorted by id.
+        Leaf(Vec<(TagId, pdf_writer::Ref)>),
+        /// An interior node whose `/Kids` are further nodes.
+        Branch(Vec<IdTreeNode>),
+    }
+
+    impl IdTreeNode {
+        /// Returns the first and last key reachable from this node, for use as the
+        /// node's `/Limits` entry.
+        pub(crate) fn limits(&self) -> (&[u8], &[u8]) {
+            match self {
+                IdTreeNode::Leaf(entries) => (
+                    entries.first().unwrap().0.as_bytes(),
+                    entries.last().unwrap().0.as_bytes(),
+                ),
+                IdTreeNode::Branch(kids) => (
+                    kids.first().unwrap().limits().0,
+                    kids.last().unwrap().limits().1,
+                ),
+            }
+        }
+    }
+
+    /// Builds a balanced `/IDTree` from every `(id, reference)` pair collected while
+    /// walking the tag tree. Returns `None` if no element carries an id, in which
+    /// case the structure tree root must omit `/IDTree` entirely.
+    pub(crate) fn build(mut entries: Vec<(TagId, pdf_writer::Ref)>) -> Option<IdTreeNode> {
+        if entries.is_empty() {
+            return None;
+        }
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let leaves: Vec<IdTreeNode> = entries
+            .chunks(LEAF_SIZE)
+            .map(|chunk| IdTreeNode::Leaf(chunk.to_vec()))
+            .collect();
+
+        if leaves.len() == 1 {
+            leaves.into_iter().next()
+        } else {
+            Some(IdTreeNode::Branch(leaves))
+        }
+    }
+}
+
+/// Support for deriving the `/Headers` attribute of a table data cell automatically
+/// from the `TableHeaderScope` and `TableCellSpan` of the table's header cells,
+/// instead of requiring callers to wire up every `THTag::with_headers` by hand.
+pub(crate) mod table_headers {
+    use super::{SmallVec, TableHeaderScope, TagId};
+    use std::collections::BTreeSet;
+
+    /// The header-related state of a single cell in a table, indexed by position
+    /// in the row-major `cells` slice passed to [`compute`].
+    #[derive(Debug, Clone)]
+    pub(crate) struct HeaderCell {
+        /// The cell's id, generated if the user didn't assign one.
+        pub(crate) id: TagId,
+        /// `Some` if the cell is a header cell (a `THTag`).
+        pub(crate) scope: Option<TableHeaderScope>,
+        /// The headers the user already assigned explicitly, if any. When set, the
+        /// automatic pass leaves the cell untouched.
+        pub(crate) explicit_headers: Option<SmallVec<[TagId; 1]>>,
+    }
+
+    /// The table's cells, expanded across their `row_span`/`col_span` into a
+    /// `rows` x `cols` grid. `grid[r][c]` is the index into `cells` of the cell
+    /// occupying that position, or `None` for positions with no cell (e.g. ragged
+    /// tables).
+    pub(crate) type Grid = Vec<Vec<Option<usize>>>;
+
+    /// Computes the automatic `/Headers` value for every data cell in `cells`,
+    /// given the table's expanded `grid`. Cells with `explicit_headers` already set
+    /// are skipped, per the existing value taking precedence over the derived one.
+    ///
+    /// For a data cell, its headers are: every header cell sharing its row with
+    /// scope `Row` or `Both`, plus every header cell sharing its column with scope
+    /// `Column` or `Both`, plus (transitively) the headers already associated with
+    /// each of those header cells. The result is deduplicated and sorted.
+    pub(crate) fn compute(grid: &Grid, cells: &[HeaderCell]) -> Vec<Option<SmallVec<[TagId; 1]>>> {
+        let mut result = vec![None; cells.len()];
+
+        for row in 0..grid.len() {
+            for col in 0..grid[row].len() {
+                let Some(idx) = grid[row][col] else {
+                    continue;
+                };
+                if cells[idx].explicit_headers.is_some() {
+                    continue;
+                }
+
+                let headers = headers_for(grid, cells, row, col, idx);
+                if !headers.is_empty() {
+                    result[idx] = Some(headers);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn headers_for(
+        grid: &Grid,
+        cells: &[HeaderCell],
+        row: usize,
+        col: usize,
+        self_idx: usize,
+    ) -> SmallVec<[TagId; 1]> {
+        let mut seen = BTreeSet::new();
+        let mut stack = Vec::new();
+
+        for &header_idx in grid[row].iter().flatten() {
+            if header_idx == self_idx {
+                continue;
+            }
+            if matches!(
+                cells[header_idx].scope,
+                Some(TableHeaderScope::Row | TableHeaderScope::Both)
+            ) {
+                stack.push(header_idx);
+            }
+        }
+        for grid_row in grid {
+            let Some(&Some(header_idx)) = grid_row.get(col) else {
+                continue;
+            };
+            if header_idx == self_idx {
+                continue;
+            }
+            if matches!(
+                cells[header_idx].scope,
+                Some(TableHeaderScope::Column | TableHeaderScope::Both)
+            ) {
+                stack.push(header_idx);
+            }
+        }
+
+        let mut result = SmallVec::new();
+        while let Some(idx) = stack.pop() {
+            if seen.insert(cells[idx].id.clone()) {
+                result.push(cells[idx].id.clone());
+            }
+            if let Some(explicit) = &cells[idx].explicit_headers {
+                for id in explicit {
+                    if seen.insert(id.clone()) {
+                        result.push(id.clone());
+                        // Follow the referenced header's own headers, if it is
+                        // a cell of this table.
+                        if let Some(next) = cells.iter().position(|cell| &cell.id == id) {
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+        }
+
+        result.sort();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ref_n(n: i32) -> pdf_writer::Ref {
+        pdf_writer::Ref::new(n)
+    }
+
+    #[test]
+    fn auto_note_ids_never_start_with_u() {
+        for i in 0..10 {
+            assert_ne!(TagId::auto_note(i).as_bytes()[0], b'U');
+        }
+    }
+
+    #[test]
+    fn auto_note_ids_are_unique_per_index() {
+        assert_ne!(TagId::auto_note(0), TagId::auto_note(1));
+    }
+
+    #[test]
+    fn id_tree_build_with_no_entries_is_none() {
+        assert!(id_tree::build(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn id_tree_build_with_one_entry_is_a_single_leaf() {
+        let id = TagId::from(*b"a");
+        let tree = id_tree::build(vec![(id.clone(), ref_n(1))]).unwrap();
+        match tree {
+            id_tree::IdTreeNode::Leaf(entries) => assert_eq!(entries, vec![(id, ref_n(1))]),
+            id_tree::IdTreeNode::Branch(_) => panic!("expected a single leaf"),
+        }
+    }
+
+    #[test]
+    fn id_tree_build_sorts_keys_lexicographically() {
+        let entries = vec![
+            (TagId::from(*b"c"), ref_n(3)),
+            (TagId::from(*b"a"), ref_n(1)),
+            (TagId::from(*b"b"), ref_n(2)),
+        ];
+        let tree = id_tree::build(entries).unwrap();
+        let id_tree::IdTreeNode::Leaf(sorted) = tree else {
+            panic!("expected a single leaf")
+        };
+        let keys: Vec<_> = sorted.iter().map(|(id, _)| id.as_bytes().to_vec()).collect();
+        let mut expected = keys.clone();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn id_tree_build_partitions_large_inputs_into_branches() {
+        let entries: Vec<_> = (0..130)
+            .map(|i| (TagId::auto(b'X', i), ref_n(i as i32)))
+            .collect();
+        let tree = id_tree::build(entries).unwrap();
+        match tree {
+            id_tree::IdTreeNode::Branch(kids) => {
+                assert_eq!(kids.len(), 3);
+                for kid in &kids {
+                    let (first, last) = kid.limits();
+                    assert!(first <= last);
+                }
+            }
+            id_tree::IdTreeNode::Leaf(_) => panic!("expected a branch for >64 entries"),
+        }
+    }
+
+    #[test]
+    fn layout_builders_push_the_expected_attrs() {
+        let tag = PTag::new()
+            .with_background_color(Color { r: 1.0, g: 0.0, b: 0.0 })
+            .with_border_color(Sides::all(Color { r: 0.0, g: 0.0, b: 0.0 }))
+            .with_border_style(Sides::all(BorderStyle::Solid))
+            .with_border_thickness(Sides::all(1.0))
+            .with_padding(Sides::all(2.0))
+            .with_color(Color { r: 0.0, g: 0.0, b: 1.0 })
+            .with_space_before(3.0)
+            .with_space_after(4.0)
+            .with_start_indent(5.0)
+            .with_end_indent(6.0)
+            .with_text_indent(7.0)
+            .with_text_align(TextAlign::Center)
+            .with_block_align(BlockAlign::Middle)
+            .with_inline_align(InlineAlign::End)
+            .with_baseline_shift(8.0)
+            .with_line_height(LineHeight::Custom(9.0))
+            .with_text_decoration_type(TextDecorationType::Underline)
+            .with_text_decoration_color(Color { r: 0.5, g: 0.5, b: 0.5 })
+            .with_text_decoration_thickness(1.5);
+
+        assert_eq!(tag.layout_attrs.len(), 19);
+        assert!(matches!(
+            tag.layout_attrs[0],
+            internal::LayoutAttr::BackgroundColor(_)
+        ));
+        assert!(matches!(
+            tag.layout_attrs.last(),
+            Some(internal::LayoutAttr::TextDecorationThickness(v)) if *v == 1.5
+        ));
     }
 }
\ No newline at end of file